@@ -0,0 +1,123 @@
+// Copyright 2021 The Chromium Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+//! One-shot JSON decoding into a [`Value`] tree.
+
+use serde::Deserialize;
+
+use crate::values::Value;
+
+/// Options controlling how `decode_json` tolerates non-strict JSON.
+///
+/// Each flag maps to one relaxation supported by the underlying
+/// `serde_json_lenient` engine. All options default to `false` (strict
+/// JSON); embedders parsing hand-edited files (e.g. config files that
+/// allow comments) opt into exactly the leniency they need.
+///
+/// Single-quoted and unquoted object keys are deliberately not offered
+/// here: the vendored `serde_json_lenient` engine has no way to parse
+/// either, so a toggle for them could only ever be a no-op or a poison
+/// pill that fails even strict input. Revisit if a future engine
+/// (upgrade or swap) adds support.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// Allow a trailing comma after the last element of an array or the
+    /// last member of an object.
+    pub allow_trailing_commas: bool,
+
+    /// Allow `//` line comments and `/* */` block comments anywhere
+    /// whitespace is permitted.
+    pub allow_comments: bool,
+
+    /// Allow raw control characters other than CR/LF (e.g. a literal
+    /// tab) to appear unescaped inside string literals.
+    pub allow_control_characters_in_strings: bool,
+
+    /// Allow a literal CR or LF inside a string literal instead of
+    /// requiring `\n`.
+    pub allow_newlines_in_strings: bool,
+
+    /// Allow `\x##` hex escapes inside string literals.
+    pub allow_x_escapes: bool,
+
+    /// Replace invalid UTF-8 byte sequences with U+FFFD (REPLACEMENT
+    /// CHARACTER) instead of failing to parse.
+    pub replace_invalid_characters: bool,
+}
+
+impl JsonOptions {
+    /// Builds a raw `serde_json_lenient` deserializer configured with
+    /// these options, for callers (such as `json::json_reader`) that
+    /// need to drive deserialization themselves rather than decoding
+    /// straight into a [`Value`].
+    pub(crate) fn deserializer_from_str<'a>(
+        self,
+        input: &'a str,
+    ) -> serde_json_lenient::Deserializer<serde_json_lenient::de::SliceRead<'a>> {
+        let mut de = serde_json_lenient::Deserializer::from_slice_with_options(
+            input.as_bytes(),
+            self.replace_invalid_characters,
+            self.allow_newlines_in_strings,
+            self.allow_control_characters_in_strings,
+            false,
+            self.allow_x_escapes,
+        );
+        de.set_ignore_trailing_commas(self.allow_trailing_commas);
+        de.set_allow_comments(self.allow_comments);
+        de
+    }
+}
+
+/// Decodes `input` as JSON, returning the materialized [`Value`] tree or
+/// a description of the first parse error encountered.
+pub fn decode_json(input: &str, options: JsonOptions) -> Result<Value, String> {
+    let mut de = options.deserializer_from_str(input);
+    Value::deserialize(&mut de).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strict_mode_rejects_trailing_comma() {
+        assert!(decode_json("[1, 2,]", JsonOptions::default()).is_err());
+    }
+
+    #[test]
+    fn lenient_mode_allows_trailing_comma() {
+        let options = JsonOptions {
+            allow_trailing_commas: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            decode_json("[1, 2,]", options).unwrap(),
+            Value::List(vec![Value::Int(1), Value::Int(2)])
+        );
+    }
+
+    #[test]
+    fn lenient_mode_allows_comments() {
+        let options = JsonOptions {
+            allow_comments: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            decode_json("// leading comment\n1", options).unwrap(),
+            Value::Int(1)
+        );
+    }
+
+    #[test]
+    fn lenient_mode_allows_hex_escapes() {
+        let options = JsonOptions {
+            allow_x_escapes: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            decode_json(r#""\x41""#, options).unwrap(),
+            Value::String("A".to_owned())
+        );
+    }
+}
@@ -0,0 +1,372 @@
+// Copyright 2021 The Chromium Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+//! Event-based views over a decoded JSON document.
+//!
+//! Neither type here reduces how much of the input the vendored
+//! `serde_json_lenient` engine has to scan: its `Deserializer` checks
+//! for a container's closing bracket immediately once a visitor
+//! returns, so every container must be read to its end regardless of
+//! how little of it the caller actually wanted. What they do avoid is
+//! materializing the parts that aren't needed into a [`Value`] tree.
+//!
+//! [`JsonEventBuffer`] reads the entire input into memory and eagerly
+//! decodes it into a flat sequence of [`JsonEvent`]s rather than a
+//! single materialized [`Value`] tree, which is convenient for callers
+//! that want to process a document as a flat stream of tokens rather
+//! than walk a tree themselves. It does **not** reduce peak memory
+//! relative to [`crate::decode_json`] — every event (including a full
+//! [`Value`] per scalar) is buffered before `next()` yields the first
+//! one. [`decode_json_subtree`] is the one with an actual memory
+//! benefit: it discards the parts of the document outside the
+//! requested branch without materializing them, though it still parses
+//! all of `input`.
+
+use std::fmt;
+use std::io::Read;
+
+use serde::de::{self, DeserializeSeed, Deserializer as _, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+
+use crate::json::json_parser::JsonOptions;
+use crate::values::Value;
+
+/// One token of a document read through [`JsonEventBuffer`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonEvent {
+    BeginObject,
+    Key(String),
+    Value(Value),
+    BeginArray,
+    EndArray,
+    EndObject,
+}
+
+/// A JSON document decoded up front into a flat queue of [`JsonEvent`]s
+/// rather than a single materialized [`Value`] tree. [`JsonEventBuffer::from_reader`]
+/// does all the reading and decoding; iterating just drains the
+/// resulting queue, it does not pull more from the original source.
+pub struct JsonEventBuffer {
+    events: std::collections::VecDeque<JsonEvent>,
+}
+
+impl JsonEventBuffer {
+    /// Reads all of `reader` and decodes it into a queue of events. Peak
+    /// memory is the whole input plus one [`JsonEvent`] per token in the
+    /// document, the same as [`crate::decode_json`]; use
+    /// [`decode_json_subtree`] instead if that's more than you want to
+    /// hold at once.
+    pub fn from_reader<R: Read>(mut reader: R, options: JsonOptions) -> Result<Self, String> {
+        let mut input = String::new();
+        reader
+            .read_to_string(&mut input)
+            .map_err(|e| e.to_string())?;
+        let mut de = options.deserializer_from_str(&input);
+        let mut events = Vec::new();
+        de.deserialize_any(EventVisitor {
+            events: &mut events,
+        })
+        .map_err(|e| e.to_string())?;
+        Ok(JsonEventBuffer {
+            events: events.into(),
+        })
+    }
+}
+
+impl Iterator for JsonEventBuffer {
+    type Item = JsonEvent;
+
+    fn next(&mut self) -> Option<JsonEvent> {
+        self.events.pop_front()
+    }
+}
+
+struct EventVisitor<'a> {
+    events: &'a mut Vec<JsonEvent>,
+}
+
+struct EventSeed<'a> {
+    events: &'a mut Vec<JsonEvent>,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for EventSeed<'a> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(EventVisitor {
+            events: self.events,
+        })
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for EventVisitor<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<(), E> {
+        self.events.push(JsonEvent::Value(Value::Bool(v)));
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<(), E> {
+        self.events.push(JsonEvent::Value(Value::Int(v)));
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<(), E> {
+        if v <= i64::MAX as u64 {
+            self.events.push(JsonEvent::Value(Value::Int(v as i64)));
+        } else {
+            self.events.push(JsonEvent::Value(Value::Double(v as f64)));
+        }
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<(), E> {
+        self.events.push(JsonEvent::Value(Value::Double(v)));
+        Ok(())
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<(), E> {
+        self.events
+            .push(JsonEvent::Value(Value::String(v.to_owned())));
+        Ok(())
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<(), E> {
+        self.events.push(JsonEvent::Value(Value::String(v)));
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        self.events.push(JsonEvent::Value(Value::Null));
+        Ok(())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.events.push(JsonEvent::BeginArray);
+        while seq
+            .next_element_seed(EventSeed {
+                events: self.events,
+            })?
+            .is_some()
+        {}
+        self.events.push(JsonEvent::EndArray);
+        Ok(())
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.events.push(JsonEvent::BeginObject);
+        while let Some(key) = map.next_key::<String>()? {
+            self.events.push(JsonEvent::Key(key));
+            map.next_value_seed(EventSeed {
+                events: self.events,
+            })?;
+        }
+        self.events.push(JsonEvent::EndObject);
+        Ok(())
+    }
+}
+
+/// Decodes only the subtree addressed by `pointer` (e.g. `"/a/b/0"`),
+/// discarding everything outside it without materializing a [`Value`]
+/// for it. This still parses the entire document — the underlying
+/// `serde_json_lenient` engine requires every container to be read up
+/// to its closing bracket before returning, so there is no way to stop
+/// scanning `input` early — but the containers outside the requested
+/// branch are read with `serde::de::IgnoredAny` rather than decoded
+/// into `Value`s, so peak allocation is bounded by the target subtree
+/// rather than the whole document. `input` must still be loaded in full
+/// by the caller before calling this function, same as
+/// [`crate::decode_json`].
+pub fn decode_json_subtree(
+    input: &str,
+    pointer: &str,
+    options: JsonOptions,
+) -> Result<Value, String> {
+    let segments: Vec<&str> = match pointer.trim_end_matches('/') {
+        "" => Vec::new(),
+        rest => rest.trim_start_matches('/').split('/').collect(),
+    };
+    let mut de = options.deserializer_from_str(input);
+    SubtreeSeed {
+        segments: &segments,
+    }
+    .deserialize(&mut de)
+    .map_err(|e| e.to_string())
+}
+
+struct SubtreeSeed<'a> {
+    segments: &'a [&'a str],
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for SubtreeSeed<'a> {
+    type Value = Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        match self.segments.split_first() {
+            None => Value::deserialize(deserializer),
+            Some((&key, rest)) => deserializer.deserialize_any(SubtreeVisitor { key, rest }),
+        }
+    }
+}
+
+struct SubtreeVisitor<'a> {
+    key: &'a str,
+    rest: &'a [&'a str],
+}
+
+impl<'de, 'a> Visitor<'de> for SubtreeVisitor<'a> {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a container holding `{}`", self.key)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        // Every entry must be consumed, even after the target key is
+        // found: the underlying deserializer checks for the map's
+        // closing brace immediately after this function returns
+        // (`deserialize_map`/`deserialize_any` call `end_map()`
+        // unconditionally), so leaving entries unread turns into a
+        // "trailing characters" parse error rather than an early return.
+        // We just discard the values we don't need via `IgnoredAny`.
+        let mut found = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if found.is_none() && key == self.key {
+                found = Some(map.next_value_seed(SubtreeSeed {
+                    segments: self.rest,
+                })?);
+            } else {
+                map.next_value::<de::IgnoredAny>()?;
+            }
+        }
+        found.ok_or_else(|| de::Error::custom(format!("key `{}` not found", self.key)))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let index: usize = self
+            .key
+            .parse()
+            .map_err(|_| de::Error::custom("expected an array index"))?;
+        // As in visit_map above, every element must be consumed so the
+        // deserializer sees the closing bracket; we discard the ones we
+        // don't need via `SeqSlot`'s `IgnoredAny` path instead of
+        // stopping once `index` is found.
+        let mut found = None;
+        let mut i = 0usize;
+        while let Some(slot) = seq.next_element_seed(SeqSlot {
+            take: i == index,
+            rest: self.rest,
+        })? {
+            found = found.or(slot);
+            i += 1;
+        }
+        found.ok_or_else(|| de::Error::custom(format!("index `{}` out of bounds", index)))
+    }
+}
+
+struct SeqSlot<'a> {
+    take: bool,
+    rest: &'a [&'a str],
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for SeqSlot<'a> {
+    type Value = Option<Value>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Option<Value>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        if self.take {
+            Ok(Some(
+                SubtreeSeed {
+                    segments: self.rest,
+                }
+                .deserialize(deserializer)?,
+            ))
+        } else {
+            de::IgnoredAny::deserialize(deserializer)?;
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_emits_flat_events() {
+        let reader =
+            JsonEventBuffer::from_reader(r#"{"a": [1, 2]}"#.as_bytes(), JsonOptions::default()).unwrap();
+        let events: Vec<JsonEvent> = reader.collect();
+        assert_eq!(
+            events,
+            vec![
+                JsonEvent::BeginObject,
+                JsonEvent::Key("a".to_owned()),
+                JsonEvent::BeginArray,
+                JsonEvent::Value(Value::Int(1)),
+                JsonEvent::Value(Value::Int(2)),
+                JsonEvent::EndArray,
+                JsonEvent::EndObject,
+            ]
+        );
+    }
+
+    #[test]
+    fn subtree_decodes_only_the_requested_branch() {
+        let input = r#"{"a": {"b": [10, 20, 30]}, "unused": "ignored"}"#;
+        let value = decode_json_subtree(input, "/a/b/1", JsonOptions::default()).unwrap();
+        assert_eq!(value, Value::Int(20));
+    }
+
+    #[test]
+    fn reader_promotes_overflowing_u64_to_double() {
+        let reader = JsonEventBuffer::from_reader(
+            u64::MAX.to_string().as_bytes(),
+            JsonOptions::default(),
+        )
+        .unwrap();
+        let events: Vec<JsonEvent> = reader.collect();
+        assert_eq!(events, vec![JsonEvent::Value(Value::Double(u64::MAX as f64))]);
+    }
+
+    #[test]
+    fn subtree_reports_missing_key() {
+        let input = r#"{"a": 1}"#;
+        assert!(decode_json_subtree(input, "/missing", JsonOptions::default()).is_err());
+    }
+
+    #[test]
+    fn subtree_still_reports_errors_in_unrelated_siblings() {
+        // The engine reads the whole document regardless of which
+        // subtree was requested, so malformed JSON anywhere is still an
+        // error even though only `/a` was asked for.
+        let input = r#"{"a": 1, "unused": {not valid json"#;
+        assert!(decode_json_subtree(input, "/a", JsonOptions::default()).is_err());
+    }
+}
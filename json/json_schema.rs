@@ -0,0 +1,507 @@
+// Copyright 2021 The Chromium Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+//! Validates a decoded [`Value`] tree against a JSON Schema document
+//! (itself a [`Value`], typically produced by [`crate::decode_json`]).
+//!
+//! The validator walks the schema and the instance in parallel,
+//! collecting every violation rather than stopping at the first one.
+//! Each [`SchemaError`] is tagged with a JSON-pointer-style path to the
+//! offending node so callers can surface actionable diagnostics for
+//! config/manifest files validated against a schema.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::{Mutex, OnceLock};
+
+use regex::Regex;
+
+use crate::values::{Value, ValueSlotRef};
+
+/// A single schema violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// JSON-pointer-style path (e.g. `/properties/0/name`) to the
+    /// instance node that failed validation.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}",
+            if self.path.is_empty() {
+                "/"
+            } else {
+                &self.path
+            },
+            self.message
+        )
+    }
+}
+
+/// Validates `instance` against `schema`, returning every violation
+/// found. An empty vec means `instance` conforms to `schema`.
+pub fn validate(schema: &ValueSlotRef, instance: &ValueSlotRef) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    let root = schema.get();
+    validate_node(root, root, instance.get(), "", &mut errors);
+    errors
+}
+
+fn validate_node(
+    root: &Value,
+    schema: &Value,
+    instance: &Value,
+    path: &str,
+    errors: &mut Vec<SchemaError>,
+) {
+    let schema = match resolve_ref(root, schema, path, errors) {
+        Some(schema) => schema,
+        None => return,
+    };
+    let dict = match schema.as_dict() {
+        Some(dict) => dict,
+        None => return,
+    };
+
+    if let Some(types) = dict.get("type") {
+        check_type(types, instance, path, errors);
+    }
+    if let Some(Value::List(allowed)) = dict.get("enum") {
+        if !allowed.contains(instance) {
+            errors.push(SchemaError {
+                path: path.to_owned(),
+                message: "value is not one of the enumerated values".to_owned(),
+            });
+        }
+    }
+
+    if let Some(inst_dict) = instance.as_dict() {
+        if let Some(Value::List(required)) = dict.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !inst_dict.contains_key(name) {
+                    errors.push(SchemaError {
+                        path: path.to_owned(),
+                        message: format!("missing required property `{}`", name),
+                    });
+                }
+            }
+        }
+
+        let properties = dict.get("properties").and_then(Value::as_dict);
+        if let Some(properties) = properties {
+            for (name, subschema) in properties {
+                if let Some(value) = inst_dict.get(name) {
+                    validate_node(root, subschema, value, &child_path(path, name), errors);
+                }
+            }
+        }
+
+        let pattern_properties = dict.get("patternProperties").and_then(Value::as_dict);
+        let mut compiled_patterns = Vec::new();
+        if let Some(pattern_properties) = pattern_properties {
+            for (pattern, subschema) in pattern_properties {
+                match Regex::new(pattern) {
+                    Ok(re) => compiled_patterns.push((re, subschema)),
+                    Err(_) => errors.push(SchemaError {
+                        path: path.to_owned(),
+                        message: format!("invalid patternProperties regex `{}`", pattern),
+                    }),
+                }
+            }
+            for (re, subschema) in &compiled_patterns {
+                for (name, value) in inst_dict {
+                    if re.is_match(name) {
+                        validate_node(root, subschema, value, &child_path(path, name), errors);
+                    }
+                }
+            }
+        }
+
+        if let Some(additional) = dict.get("additionalProperties") {
+            for (name, value) in inst_dict {
+                let covered_by_properties = properties.is_some_and(|p| p.contains_key(name));
+                let covered_by_pattern = compiled_patterns.iter().any(|(re, _)| re.is_match(name));
+                if covered_by_properties || covered_by_pattern {
+                    continue;
+                }
+                match additional {
+                    Value::Bool(false) => errors.push(SchemaError {
+                        path: child_path(path, name),
+                        message: "additional property is not allowed".to_owned(),
+                    }),
+                    Value::Dict(_) => {
+                        validate_node(root, additional, value, &child_path(path, name), errors)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if let Some(inst_list) = instance.as_list() {
+        if let Some(items) = dict.get("items") {
+            match items {
+                Value::List(tuple_schemas) => {
+                    for (index, item) in inst_list.iter().enumerate() {
+                        if let Some(subschema) = tuple_schemas.get(index) {
+                            validate_node(
+                                root,
+                                subschema,
+                                item,
+                                &child_path(path, &index.to_string()),
+                                errors,
+                            );
+                        }
+                    }
+                }
+                _ => {
+                    for (index, item) in inst_list.iter().enumerate() {
+                        validate_node(
+                            root,
+                            items,
+                            item,
+                            &child_path(path, &index.to_string()),
+                            errors,
+                        );
+                    }
+                }
+            }
+        }
+        if let Some(min) = dict.get("minItems").and_then(Value::as_f64) {
+            if (inst_list.len() as f64) < min {
+                errors.push(SchemaError {
+                    path: path.to_owned(),
+                    message: format!("expected at least {} items", min),
+                });
+            }
+        }
+        if let Some(max) = dict.get("maxItems").and_then(Value::as_f64) {
+            if (inst_list.len() as f64) > max {
+                errors.push(SchemaError {
+                    path: path.to_owned(),
+                    message: format!("expected at most {} items", max),
+                });
+            }
+        }
+    }
+
+    if let Some(number) = instance.as_f64() {
+        if let Some(min) = dict.get("minimum").and_then(Value::as_f64) {
+            if number < min {
+                errors.push(SchemaError {
+                    path: path.to_owned(),
+                    message: format!("expected >= {}, found {}", min, number),
+                });
+            }
+        }
+        if let Some(max) = dict.get("maximum").and_then(Value::as_f64) {
+            if number > max {
+                errors.push(SchemaError {
+                    path: path.to_owned(),
+                    message: format!("expected <= {}, found {}", max, number),
+                });
+            }
+        }
+    }
+
+    if let Some(s) = instance.as_str() {
+        let len = s.chars().count() as f64;
+        if let Some(min) = dict.get("minLength").and_then(Value::as_f64) {
+            if len < min {
+                errors.push(SchemaError {
+                    path: path.to_owned(),
+                    message: format!("expected length >= {}, found {}", min, len),
+                });
+            }
+        }
+        if let Some(max) = dict.get("maxLength").and_then(Value::as_f64) {
+            if len > max {
+                errors.push(SchemaError {
+                    path: path.to_owned(),
+                    message: format!("expected length <= {}, found {}", max, len),
+                });
+            }
+        }
+        if let Some(pattern) = dict.get("pattern").and_then(Value::as_str) {
+            match Regex::new(pattern) {
+                Ok(re) if !re.is_match(s) => errors.push(SchemaError {
+                    path: path.to_owned(),
+                    message: format!("value does not match pattern `{}`", pattern),
+                }),
+                Err(_) => errors.push(SchemaError {
+                    path: path.to_owned(),
+                    message: format!("invalid pattern regex `{}`", pattern),
+                }),
+                _ => {}
+            }
+        }
+        if let Some(format) = dict.get("format").and_then(Value::as_str) {
+            if check_format(format, s) == Some(false) {
+                errors.push(SchemaError {
+                    path: path.to_owned(),
+                    message: format!("value does not match format `{}`", format),
+                });
+            }
+        }
+    }
+}
+
+/// A caller-supplied checker for the JSON Schema `format` keyword.
+/// Receives the string instance and returns whether it conforms.
+type FormatChecker = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+fn format_registry() -> &'static Mutex<HashMap<String, FormatChecker>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, FormatChecker>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut registry: HashMap<String, FormatChecker> = HashMap::new();
+        registry.insert(
+            "uri".to_owned(),
+            Box::new(check_uri_format as fn(&str) -> bool),
+        );
+        registry.insert(
+            "email".to_owned(),
+            Box::new(check_email_format as fn(&str) -> bool),
+        );
+        registry.insert(
+            "ipv4".to_owned(),
+            Box::new(check_ipv4_format as fn(&str) -> bool),
+        );
+        registry.insert(
+            "ipv6".to_owned(),
+            Box::new(check_ipv6_format as fn(&str) -> bool),
+        );
+        registry.insert(
+            "date-time".to_owned(),
+            Box::new(check_date_time_format as fn(&str) -> bool),
+        );
+        Mutex::new(registry)
+    })
+}
+
+/// Registers a checker for the `format` keyword under `name`, replacing
+/// any existing checker registered under that name (including a
+/// built-in one). This lets embedders add domain-specific formats
+/// without patching the crate.
+pub fn register_format(name: &str, checker: Box<dyn Fn(&str) -> bool + Send + Sync>) {
+    format_registry()
+        .lock()
+        .unwrap()
+        .insert(name.to_owned(), checker);
+}
+
+/// Returns `None` for a format name with no registered checker (treated
+/// as an unenforced annotation, per the JSON Schema spec), or `Some` of
+/// whether `value` conforms to the named format.
+fn check_format(name: &str, value: &str) -> Option<bool> {
+    format_registry()
+        .lock()
+        .unwrap()
+        .get(name)
+        .map(|checker| checker(value))
+}
+
+fn check_uri_format(value: &str) -> bool {
+    match url::Url::parse(value) {
+        Ok(url) => url.host().is_some(),
+        Err(_) => false,
+    }
+}
+
+fn check_email_format(value: &str) -> bool {
+    Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$")
+        .unwrap()
+        .is_match(value)
+}
+
+fn check_ipv4_format(value: &str) -> bool {
+    value.parse::<Ipv4Addr>().is_ok()
+}
+
+fn check_ipv6_format(value: &str) -> bool {
+    value.parse::<Ipv6Addr>().is_ok()
+}
+
+fn check_date_time_format(value: &str) -> bool {
+    Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})$")
+        .unwrap()
+        .is_match(value)
+}
+
+/// Resolves a `$ref` against `root`'s `#/definitions/...`, returning the
+/// target schema. Returns `None` (after recording an error) if the
+/// pointer cannot be resolved.
+fn resolve_ref<'a>(
+    root: &'a Value,
+    schema: &'a Value,
+    path: &str,
+    errors: &mut Vec<SchemaError>,
+) -> Option<&'a Value> {
+    let dict = schema.as_dict()?;
+    let Some(reference) = dict.get("$ref").and_then(Value::as_str) else {
+        return Some(schema);
+    };
+    match resolve_pointer(root, reference) {
+        Some(target) => Some(target),
+        None => {
+            errors.push(SchemaError {
+                path: path.to_owned(),
+                message: format!("unresolved $ref `{}`", reference),
+            });
+            None
+        }
+    }
+}
+
+fn resolve_pointer<'a>(root: &'a Value, pointer: &str) -> Option<&'a Value> {
+    let rest = pointer.strip_prefix("#/")?;
+    let mut node = root;
+    for segment in rest.split('/') {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        node = node.as_dict()?.get(&segment)?;
+    }
+    Some(node)
+}
+
+fn check_type(types: &Value, instance: &Value, path: &str, errors: &mut Vec<SchemaError>) {
+    let allowed: Vec<&str> = match types {
+        Value::String(name) => vec![name.as_str()],
+        Value::List(names) => names.iter().filter_map(Value::as_str).collect(),
+        _ => return,
+    };
+    if !allowed.iter().any(|name| type_matches(name, instance)) {
+        errors.push(SchemaError {
+            path: path.to_owned(),
+            message: format!(
+                "expected type `{}`, found `{}`",
+                allowed.join(" or "),
+                instance.type_name()
+            ),
+        });
+    }
+}
+
+fn type_matches(expected: &str, instance: &Value) -> bool {
+    match expected {
+        "integer" => matches!(instance, Value::Int(_)),
+        "number" => matches!(instance, Value::Int(_) | Value::Double(_)),
+        other => other == instance.type_name(),
+    }
+}
+
+fn child_path(parent: &str, name: &str) -> String {
+    format!("{}/{}", parent, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_json;
+    use crate::json::json_parser::JsonOptions;
+
+    fn decode(input: &str) -> Value {
+        decode_json(input, JsonOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn required_property_missing() {
+        let schema = decode(r#"{"type": "object", "required": ["name"]}"#);
+        let instance = decode(r#"{}"#);
+        let errors = validate(&ValueSlotRef::new(&schema), &ValueSlotRef::new(&instance));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("name"));
+    }
+
+    #[test]
+    fn type_mismatch_is_reported() {
+        let schema = decode(r#"{"type": "string"}"#);
+        let instance = decode("42");
+        let errors = validate(&ValueSlotRef::new(&schema), &ValueSlotRef::new(&instance));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "");
+    }
+
+    #[test]
+    fn nested_properties_report_pointer_path() {
+        let schema = decode(
+            r#"{"type": "object", "properties": {"age": {"type": "integer", "minimum": 0}}}"#,
+        );
+        let instance = decode(r#"{"age": -1}"#);
+        let errors = validate(&ValueSlotRef::new(&schema), &ValueSlotRef::new(&instance));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "/age");
+    }
+
+    #[test]
+    fn ref_resolves_against_definitions() {
+        let schema = decode(
+            r##"{
+                "definitions": {"Name": {"type": "string", "minLength": 1}},
+                "properties": {"name": {"$ref": "#/definitions/Name"}}
+            }"##,
+        );
+        let instance = decode(r#"{"name": ""}"#);
+        let errors = validate(&ValueSlotRef::new(&schema), &ValueSlotRef::new(&instance));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "/name");
+    }
+
+    #[test]
+    fn valid_instance_has_no_errors() {
+        let schema = decode(
+            r#"{"type": "object", "properties": {"n": {"type": "integer"}}, "required": ["n"]}"#,
+        );
+        let instance = decode(r#"{"n": 1}"#);
+        let errors = validate(&ValueSlotRef::new(&schema), &ValueSlotRef::new(&instance));
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn uri_format_rejects_malformed_url() {
+        let schema = decode(r#"{"type": "string", "format": "uri"}"#);
+        let instance = decode(r#""not a url""#);
+        let errors = validate(&ValueSlotRef::new(&schema), &ValueSlotRef::new(&instance));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("format"));
+    }
+
+    #[test]
+    fn uri_format_accepts_absolute_url() {
+        let schema = decode(r#"{"type": "string", "format": "uri"}"#);
+        let instance = decode(r#""https://example.com/path""#);
+        let errors = validate(&ValueSlotRef::new(&schema), &ValueSlotRef::new(&instance));
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn pattern_properties_cover_additional_properties_check() {
+        let schema = decode(
+            r#"{
+                "type": "object",
+                "patternProperties": {"^x-": {"type": "string"}},
+                "additionalProperties": false
+            }"#,
+        );
+        let instance = decode(r#"{"x-custom": "ok", "other": 1}"#);
+        let errors = validate(&ValueSlotRef::new(&schema), &ValueSlotRef::new(&instance));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "/other");
+    }
+
+    #[test]
+    fn custom_format_can_be_registered() {
+        register_format(
+            "even-digits",
+            Box::new(|s| s.chars().all(|c| c.is_ascii_digit()) && s.len() % 2 == 0),
+        );
+        let schema = decode(r#"{"type": "string", "format": "even-digits"}"#);
+        let instance = decode(r#""123""#);
+        let errors = validate(&ValueSlotRef::new(&schema), &ValueSlotRef::new(&instance));
+        assert_eq!(errors.len(), 1);
+    }
+}
@@ -0,0 +1,12 @@
+// Copyright 2021 The Chromium Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+//! JSON decoding built on top of `serde_json_lenient`, plus validation
+//! and event/subtree reading utilities layered over the decoded
+//! [`crate::values::Value`] tree. The reading utilities are not
+//! incremental: each one reads and parses the whole input.
+
+pub mod json_parser;
+pub mod json_reader;
+pub mod json_schema;
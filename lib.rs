@@ -8,6 +8,8 @@ mod values;
 mod values_deserialization;
 
 pub use json::json_parser::{decode_json, JsonOptions};
-pub use values::ValueSlotRef;
+pub use json::json_reader::{decode_json_subtree, JsonEvent, JsonEventBuffer};
+pub use json::json_schema::{register_format, validate, SchemaError};
+pub use values::{Value, ValueSlotRef};
 
 pub use rs_glue::ffi::NewValueSlotForTesting;
@@ -0,0 +1,23 @@
+// Copyright 2021 The Chromium Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+//! The `cxx` bridge definition shared with the C++ side of `//base`.
+//!
+//! `ValueSlot` is an opaque handle to a `base::Value` owned by C++; it
+//! lets Rust hold a stable reference to a value that was constructed on
+//! the C++ side without copying it across the FFI boundary.
+
+#[cxx::bridge(namespace = "base::rs_glue")]
+pub mod ffi {
+    unsafe extern "C++" {
+        include!("base/rs_glue/value_slot.h");
+
+        type ValueSlot;
+
+        /// Constructs an empty `ValueSlot` for use in Rust-side unit
+        /// tests that need a slot to hand to FFI entry points without
+        /// going through the full C++ test harness.
+        fn NewValueSlotForTesting() -> UniquePtr<ValueSlot>;
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright 2021 The Chromium Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+//! A pure-Rust mirror of Chromium's `base::Value`, plus a cheap borrowed
+//! view (`ValueSlotRef`) used to walk a decoded tree without cloning.
+
+use std::collections::BTreeMap;
+
+/// A decoded JSON/`base::Value` tree.
+///
+/// This mirrors the six fundamental types that `base::Value` supports.
+/// Chromium's `base::Value` does not distinguish integers from doubles at
+/// the type level (both are `DOUBLE`/`INTEGER` variants of a numeric
+/// union); we keep them apart here because JSON Schema's `type` keyword
+/// distinguishes `"integer"` from `"number"`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Double(f64),
+    String(String),
+    List(Vec<Value>),
+    Dict(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// The JSON Schema `type` name for this value (the schema validator
+    /// matches `"number"` against both `Int` and `Double` and `"integer"`
+    /// against `Int` only; this returns the more specific name).
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Int(_) => "integer",
+            Value::Double(_) => "number",
+            Value::String(_) => "string",
+            Value::List(_) => "array",
+            Value::Dict(_) => "object",
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(i) => Some(*i as f64),
+            Value::Double(d) => Some(*d),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(l) => Some(l),
+            _ => None,
+        }
+    }
+
+    pub fn as_dict(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            Value::Dict(d) => Some(d),
+            _ => None,
+        }
+    }
+}
+
+/// A borrowed view onto a node of a [`Value`] tree.
+///
+/// Code that only needs to inspect a decoded document (such as schema
+/// validation) takes `ValueSlotRef`
+/// rather than `&Value` so that the representation backing a node can
+/// later be swapped out (e.g. for a C++-owned `base::Value` reached over
+/// the `rs_glue` bridge) without touching call sites.
+#[derive(Debug, Clone, Copy)]
+pub struct ValueSlotRef<'a> {
+    value: &'a Value,
+}
+
+impl<'a> ValueSlotRef<'a> {
+    pub fn new(value: &'a Value) -> Self {
+        ValueSlotRef { value }
+    }
+
+    pub fn get(&self) -> &'a Value {
+        self.value
+    }
+}
+
+impl<'a> From<&'a Value> for ValueSlotRef<'a> {
+    fn from(value: &'a Value) -> Self {
+        ValueSlotRef::new(value)
+    }
+}